@@ -0,0 +1,13 @@
+#![no_main]
+
+// Differential fuzz target: feed arbitrary input vectors to the batch,
+// transport, AI, vector-add and weighted-average kernels and compare each
+// against its naive scalar reference. Run with
+// `cargo +nightly fuzz run differential` from the `fuzz/` directory.
+
+use libfuzzer_sys::fuzz_target;
+use qarbon_wasm::differential::{run, DiffInput};
+
+fuzz_target!(|input: DiffInput| {
+    run(&input);
+});